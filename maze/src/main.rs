@@ -1,4 +1,5 @@
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::collections::BinaryHeap;
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -19,6 +20,7 @@ struct Rect {
     y: f64,
     width: f64,
     height: f64,
+    cost: Option<i32>,
 }
 
 #[derive(Debug)]
@@ -42,6 +44,14 @@ struct Maze {
     end: (f64, f64),
     size: (f64, f64),
     walls: Vec<(f64, f64, f64, f64)>,
+    costs: Vec<(f64, f64, f64, f64, i32)>,
+    /// Key pickups: `(cx, cy, label)`, label being the part of the SVG id
+    /// after `key-`.
+    keys: Vec<(f64, f64, String)>,
+    /// Locked doors: `(x, y, width, height, label)`, label being the part
+    /// of the SVG id after `door-`; a door is only passable once the key
+    /// with the matching label has been collected.
+    doors: Vec<(f64, f64, f64, f64, String)>,
 }
 
 impl Maze {
@@ -52,6 +62,9 @@ impl Maze {
             end: (0.0, 0.0),
             size: (0.0, 0.0),
             walls: vec![],
+            costs: vec![],
+            keys: vec![],
+            doors: vec![],
         }
     }
 
@@ -71,6 +84,64 @@ impl Maze {
             }
         }
 
+        let mut costs = HashMap::new();
+
+        for c in self.costs.iter() {
+            let min_x = (c.0 - self.origin.0).floor() as i32;
+            let min_y = (c.1 - self.origin.1).floor() as i32;
+            let max_x = (c.0 - self.origin.0 + c.2).ceil() as i32;
+            let max_y = (c.1 - self.origin.1 + c.3).ceil() as i32;
+
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    costs.insert((x, y), c.4);
+                }
+            }
+        }
+
+        // Assign each distinct key/door label a bit in the `keys_held`
+        // bitset, in label order so the mapping is deterministic.
+        let mut labels = BTreeSet::new();
+        for (_, _, label) in self.keys.iter() {
+            labels.insert(label.clone());
+        }
+        for (_, _, _, _, label) in self.doors.iter() {
+            labels.insert(label.clone());
+        }
+        assert!(
+            labels.len() <= 32,
+            "at most 32 distinct key/door labels are supported (got {})",
+            labels.len()
+        );
+        let bits: HashMap<String, u32> = labels
+            .into_iter()
+            .enumerate()
+            .map(|(i, label)| (label, 1u32 << i))
+            .collect();
+
+        let mut keys = HashMap::new();
+        for (cx, cy, label) in self.keys.iter() {
+            let coord = (
+                (cx - self.origin.0).round() as i32,
+                (cy - self.origin.1).round() as i32,
+            );
+            keys.insert(coord, bits[label]);
+        }
+
+        let mut doors = HashMap::new();
+        for (x, y, w, h, label) in self.doors.iter() {
+            let min_x = (x - self.origin.0).floor() as i32;
+            let min_y = (y - self.origin.1).floor() as i32;
+            let max_x = (x - self.origin.0 + w).ceil() as i32;
+            let max_y = (y - self.origin.1 + h).ceil() as i32;
+
+            for gy in min_y..max_y {
+                for gx in min_x..max_x {
+                    doors.insert((gx, gy), bits[label]);
+                }
+            }
+        }
+
         Grid {
             start: (
                 (self.start.0 - self.origin.0).round() as i32,
@@ -83,16 +154,93 @@ impl Maze {
             width: self.size.0.ceil() as i32,
             height: self.size.1.ceil() as i32,
             walls,
+            costs,
+            keys,
+            doors,
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+enum Direction {
+    None,
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    fn from_delta(delta: (i32, i32)) -> Self {
+        match delta {
+            (0, -1) => Direction::Up,
+            (0, 1) => Direction::Down,
+            (-1, 0) => Direction::Left,
+            (1, 0) => Direction::Right,
+            (-1, -1) => Direction::UpLeft,
+            (1, -1) => Direction::UpRight,
+            (-1, 1) => Direction::DownLeft,
+            (1, 1) => Direction::DownRight,
+            _ => Direction::None,
         }
     }
+
+    fn is_opposite(&self, other: &Direction) -> bool {
+        matches!(
+            (self, other),
+            (Direction::Up, Direction::Down)
+                | (Direction::Down, Direction::Up)
+                | (Direction::Left, Direction::Right)
+                | (Direction::Right, Direction::Left)
+                | (Direction::UpLeft, Direction::DownRight)
+                | (Direction::DownRight, Direction::UpLeft)
+                | (Direction::UpRight, Direction::DownLeft)
+                | (Direction::DownLeft, Direction::UpRight)
+        )
+    }
+}
+
+/// Connectivity mode for `Grid::path`: four orthogonal neighbors, or those
+/// plus the four diagonals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Connectivity {
+    Four,
+    Eight,
+}
+
+/// Cost of an orthogonal step, and of a diagonal step (~`sqrt(2)` times as
+/// much), scaled to integers so both costs and the heuristic can stay in
+/// `i32`.
+const ORTHOGONAL_COST: i32 = 10;
+const DIAGONAL_COST: i32 = 14;
+
+fn step_cost(delta: (i32, i32)) -> i32 {
+    if delta.0 != 0 && delta.1 != 0 {
+        DIAGONAL_COST
+    } else {
+        ORTHOGONAL_COST
+    }
 }
 
+/// A search state: the cell occupied, the direction the agent arrived from
+/// (`Direction::None` at the start), how many consecutive cells it has
+/// travelled in that direction, and the bitset of keys collected so far.
+type State = ((i32, i32), Direction, i32, u32);
+
+/// Called after every expansion during `Grid::path`, with the expansion
+/// count so far and the search state at that point.
+type ExpansionCallback<'a> = &'a mut dyn FnMut(usize, &HashMap<State, Node>, &HashSet<(i32, i32)>);
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 struct Node {
-    coord: (i32, i32),
+    state: State,
     f_score: i32,
     g_score: i32,
-    came_from: (i32, i32),
+    came_from: State,
 }
 
 impl Ord for Node {
@@ -110,8 +258,28 @@ impl PartialOrd for Node {
     }
 }
 
-fn d(a: (i32, i32), b: (i32, i32)) -> i32 {
-    (a.0 - b.0) + (a.1 - b.1)
+/// Admissible distance heuristic in the same units as `step_cost`: Manhattan
+/// distance for 4-connectivity, octile distance for 8-connectivity.
+fn heuristic(a: (i32, i32), b: (i32, i32), connectivity: Connectivity) -> i32 {
+    let dx = (a.0 - b.0).abs();
+    let dy = (a.1 - b.1).abs();
+    match connectivity {
+        Connectivity::Four => ORTHOGONAL_COST * (dx + dy),
+        Connectivity::Eight => {
+            ORTHOGONAL_COST * (dx + dy) + (DIAGONAL_COST - 2 * ORTHOGONAL_COST) * dx.min(dy)
+        }
+    }
+}
+
+/// Richer output from `Grid::path`, for tools that want to visualize the
+/// search itself rather than just the final route.
+#[derive(Debug)]
+struct SearchResult {
+    path: Vec<(i32, i32)>,
+    /// Every state discovered during the search, with its final g/f scores.
+    nodes: HashMap<State, Node>,
+    /// Cells popped from `open_set` (the closed set), i.e. fully expanded.
+    expanded: HashSet<(i32, i32)>,
 }
 
 #[derive(Debug)]
@@ -121,43 +289,111 @@ struct Grid {
     width: i32,
     height: i32,
     walls: HashSet<(i32, i32)>,
+    costs: HashMap<(i32, i32), i32>,
+    /// Key cell -> the bit it sets in `keys_held` when collected.
+    keys: HashMap<(i32, i32), u32>,
+    /// Door cell -> the bit that must already be set in `keys_held` to
+    /// pass through it.
+    doors: HashMap<(i32, i32), u32>,
 }
 
 impl Grid {
-    fn path(&self) -> Vec<(i32, i32)> {
+    /// Traversal cost of `coord`, defaulting to 1 for cells with no
+    /// explicit `data-cost` region.
+    fn cost_of(&self, coord: (i32, i32)) -> i32 {
+        *self.costs.get(&coord).unwrap_or(&1)
+    }
+
+    /// Lower bound on any cell's traversal cost: the smallest explicit cost,
+    /// or the default of 1 if that is lower. `heuristic` is scaled by it so
+    /// it never overestimates; when every cost is 1 or more this is 1 and
+    /// the heuristic is used unscaled, and a zero-cost region turns the
+    /// search into plain Dijkstra.
+    fn min_cost(&self) -> i32 {
+        self.costs
+            .values()
+            .copied()
+            .chain(std::iter::once(1))
+            .min()
+            .unwrap_or(1)
+    }
+
+    /// Bitset with every key in the maze set, used to require a full
+    /// key collection before the goal is accepted.
+    fn all_keys_mask(&self) -> u32 {
+        self.keys.values().fold(0, |acc, bit| acc | bit)
+    }
+
+    /// Find the shortest path from `start` to `end`, honoring a "crucible"
+    /// style turn constraint: the agent must travel at least `min_straight`
+    /// cells in a straight line before it is allowed to turn, and at most
+    /// `max_straight` cells straight before it is forced to turn.
+    /// `min_straight: 0` and `max_straight: i32::MAX` reproduce the
+    /// unconstrained behavior. Doors are only passable once the matching
+    /// key has been collected; if `require_all_keys` is set the goal is
+    /// only accepted once every key in the maze has been picked up.
+    ///
+    /// `on_expansion`, if given, is called after every cell is popped from
+    /// `open_set` with the expansion count so far and the search state at
+    /// that point, so a caller can render frontier-growth animation frames.
+    fn path(
+        &self,
+        min_straight: i32,
+        max_straight: i32,
+        require_all_keys: bool,
+        connectivity: Connectivity,
+        mut on_expansion: Option<ExpansionCallback>,
+    ) -> SearchResult {
+        let min_cost = self.min_cost();
+        let all_keys = self.all_keys_mask();
+
         let mut open_set = BinaryHeap::new();
         let mut open_set_map = HashSet::new();
+        let start_keys = self.keys.get(&self.start).copied().unwrap_or(0);
+        let start_state: State = (self.start, Direction::None, 0, start_keys);
         let start = Node {
-            f_score: d(self.start, self.end),
+            f_score: min_cost * heuristic(self.start, self.end, connectivity),
             g_score: 0,
-            came_from: self.start,
-            coord: self.start,
+            came_from: start_state,
+            state: start_state,
         };
-        open_set_map.insert(start.coord);
+        open_set_map.insert(start.state);
         open_set.push(start.clone());
 
         let mut nodes = HashMap::new();
-        nodes.insert(start.coord, start);
+        nodes.insert(start.state, start);
 
-        let neighbors = |node: &Node| {
-            let c = node.coord;
-            let neighbors = vec![
+        let neighbors = |coord: (i32, i32)| {
+            let c = coord;
+            let mut candidates = vec![
                 (c.0 - 1, c.1),
                 (c.0 + 1, c.1),
                 (c.0, c.1 - 1),
                 (c.0, c.1 + 1),
             ];
 
-            let coords: Vec<_> = neighbors
+            if connectivity == Connectivity::Eight {
+                // Reject a diagonal when both flanking orthogonal cells are
+                // walls, so the path can't cut across a wall corner.
+                let corners = [
+                    ((c.0 - 1, c.1 - 1), (c.0 - 1, c.1), (c.0, c.1 - 1)),
+                    ((c.0 + 1, c.1 - 1), (c.0 + 1, c.1), (c.0, c.1 - 1)),
+                    ((c.0 - 1, c.1 + 1), (c.0 - 1, c.1), (c.0, c.1 + 1)),
+                    ((c.0 + 1, c.1 + 1), (c.0 + 1, c.1), (c.0, c.1 + 1)),
+                ];
+                for (diag, flank_a, flank_b) in corners {
+                    if !(self.walls.contains(&flank_a) && self.walls.contains(&flank_b)) {
+                        candidates.push(diag);
+                    }
+                }
+            }
+
+            let coords: Vec<_> = candidates
                 .into_iter()
                 .filter(|c| {
                     if self.walls.contains(c) {
                         false
-                    } else if c.0 < 0
-                        || c.1 < 0
-                        || c.0 >= self.width
-                        || c.1 >= self.height
-                    {
+                    } else if c.0 < 0 || c.1 < 0 || c.0 >= self.width || c.1 >= self.height {
                         false
                     } else {
                         true
@@ -169,28 +405,67 @@ impl Grid {
         };
 
         let mut found = None;
+        let mut expanded = HashSet::new();
+        let mut expansions: usize = 0;
         while !open_set.is_empty() {
             let current = open_set.pop().unwrap();
-            open_set_map.remove(&current.coord);
-            if current.coord == self.end {
+            open_set_map.remove(&current.state);
+            expanded.insert(current.state.0);
+            expansions += 1;
+            if let Some(cb) = on_expansion.as_deref_mut() {
+                cb(expansions, &nodes, &expanded);
+            }
+            let (coord, dir, run, keys_held) = current.state;
+            if coord == self.end
+                && run >= min_straight
+                && (!require_all_keys || keys_held == all_keys)
+            {
                 found = Some(current);
                 break;
             }
 
-            for ncoord in neighbors(&current) {
-                let tentative = current.g_score + 1;
-                let n = nodes.entry(ncoord).or_insert(Node {
+            for ncoord in neighbors(coord) {
+                #[allow(clippy::collapsible_if)]
+                if let Some(&needed) = self.doors.get(&ncoord) {
+                    if keys_held & needed != needed {
+                        continue;
+                    }
+                }
+
+                let delta = (ncoord.0 - coord.0, ncoord.1 - coord.1);
+                let ndir = Direction::from_delta(delta);
+
+                let nrun = if dir == Direction::None {
+                    1
+                } else if ndir == dir {
+                    if run >= max_straight {
+                        continue;
+                    }
+                    run + 1
+                } else if ndir.is_opposite(&dir) {
+                    continue;
+                } else {
+                    if run < min_straight {
+                        continue;
+                    }
+                    1
+                };
+
+                let nkeys = keys_held | self.keys.get(&ncoord).copied().unwrap_or(0);
+                let nstate: State = (ncoord, ndir, nrun, nkeys);
+                let tentative = current.g_score + self.cost_of(ncoord) * step_cost(delta);
+                let n = nodes.entry(nstate).or_insert(Node {
                     f_score: i32::MAX,
                     g_score: i32::MAX,
-                    coord: ncoord,
-                    came_from: current.coord,
+                    state: nstate,
+                    came_from: current.state,
                 });
                 if tentative < n.g_score {
-                    n.came_from = current.coord;
+                    n.came_from = current.state;
                     n.g_score = tentative;
-                    n.f_score = tentative + d(self.end, n.coord);
-                    if !open_set_map.contains(&n.coord) {
-                        open_set_map.insert(n.coord);
+                    n.f_score = tentative + min_cost * heuristic(ncoord, self.end, connectivity);
+                    if !open_set_map.contains(&n.state) {
+                        open_set_map.insert(n.state);
                         open_set.push(n.clone());
                     }
                 }
@@ -199,31 +474,87 @@ impl Grid {
 
         let reconstruct = |node: &Node| {
             let mut path = Vec::new();
-            let mut coord = node.coord;
-            while coord != self.start {
-                path.push(coord);
-                coord = nodes.get(&coord).unwrap().came_from;
+            let mut state = node.state;
+            while state != start_state {
+                path.push(state.0);
+                state = nodes.get(&state).unwrap().came_from;
             }
             path.push(self.start);
             path
         };
 
-        if let Some(found) = found {
+        let path = if let Some(found) = &found {
             println!("Finished path: {:?}", found);
-            return reconstruct(&found);
+            println!("Path cost: {}", found.g_score);
+            reconstruct(found)
         } else {
             println!("No solution found!!!");
-            return vec![];
+            vec![]
+        };
+
+        SearchResult {
+            path,
+            nodes,
+            expanded,
         }
     }
 }
 
+/// Traversal-cost tiles for the frontier/closed-set heatmap: blue for
+/// expanded (closed) cells, orange for cells still on the frontier, with
+/// opacity rising linearly from 0.1 to 0.5 with the cheapest known
+/// `g_score` at that cell, relative to the largest one.
+fn heatmap_tiles(
+    nodes: &HashMap<State, Node>,
+    expanded: &HashSet<(i32, i32)>,
+) -> Vec<element::Rectangle> {
+    let mut coord_g: HashMap<(i32, i32), i32> = HashMap::new();
+    for node in nodes.values() {
+        coord_g
+            .entry(node.state.0)
+            .and_modify(|g| *g = (*g).min(node.g_score))
+            .or_insert(node.g_score);
+    }
+    let max_g = coord_g.values().copied().max().unwrap_or(1).max(1);
+
+    coord_g
+        .iter()
+        .map(|(coord, g)| {
+            let intensity = *g as f64 / max_g as f64;
+            let color = if expanded.contains(coord) {
+                format!("rgba(0, 0, 255, {:.2})", 0.1 + 0.4 * intensity)
+            } else {
+                format!("rgba(255, 165, 0, {:.2})", 0.1 + 0.4 * intensity)
+            };
+            element::Rectangle::new()
+                .set("fill", color)
+                .set("stroke", "transparent")
+                .set("x", coord.0)
+                .set("y", coord.1)
+                .set("width", 1)
+                .set("height", 1)
+        })
+        .collect()
+}
+
 use svg::Document;
 
 use svg::node::element;
 use svg::node::element::tag;
 use svg::parser::Event;
 
+/// Crucible-style turn constraint passed to `Grid::path`: the agent must
+/// travel at least `MIN_STRAIGHT` cells straight before turning, and at
+/// most `MAX_STRAIGHT` cells straight before being forced to turn.
+/// `(0, i32::MAX)` reproduces unconstrained movement.
+const MIN_STRAIGHT: i32 = 0;
+const MAX_STRAIGHT: i32 = i32::MAX;
+
+/// Movement model passed to `Grid::path`: `Four` allows only orthogonal
+/// steps, `Eight` also allows diagonals (at `DIAGONAL_COST`, with
+/// corner-cutting rejected).
+const CONNECTIVITY: Connectivity = Connectivity::Four;
+
 fn main() -> Result<(), MazeError> {
     let mut content = String::new();
     let mut attrs = Vec::new();
@@ -236,6 +567,7 @@ fn main() -> Result<(), MazeError> {
                     y: attributes.get("y").unwrap().parse().unwrap(),
                     width: attributes.get("width").unwrap().parse().unwrap(),
                     height: attributes.get("height").unwrap().parse().unwrap(),
+                    cost: attributes.get("data-cost").and_then(|v| v.parse().ok()),
                 });
                 attrs.push(r);
             }
@@ -261,12 +593,19 @@ fn main() -> Result<(), MazeError> {
                     maze.start = (c.cx, c.cy)
                 } else if c.id == "end" {
                     maze.end = (c.cx, c.cy)
+                } else if let Some(label) = c.id.strip_prefix("key-") {
+                    maze.keys.push((c.cx, c.cy, label.to_string()));
                 }
             }
             Attr::Rect(r) => {
                 if r.id == "bg" {
                     maze.origin = (r.x, r.y);
                     maze.size = (r.width, r.height);
+                } else if let Some(label) = r.id.strip_prefix("door-") {
+                    maze.doors
+                        .push((r.x, r.y, r.width, r.height, label.to_string()));
+                } else if let Some(cost) = r.cost {
+                    maze.costs.push((r.x, r.y, r.width, r.height, cost));
                 } else {
                     maze.walls.push((r.x, r.y, r.width, r.height));
                 }
@@ -275,10 +614,32 @@ fn main() -> Result<(), MazeError> {
     }
 
     let grid = maze.grid();
-    let path = grid.path();
 
-    let mut document =
-        Document::new().set("viewBox", (0, 0, maze.size.0, maze.size.1));
+    // Set above 0 to also dump ../frames/frame-NNNNN.svg every K expansions,
+    // for an animation of the frontier growing.
+    let frame_interval: usize = 0;
+    let mut frame_cb =
+        |count: usize, nodes: &HashMap<State, Node>, expanded: &HashSet<(i32, i32)>| {
+            if frame_interval == 0 || !count.is_multiple_of(frame_interval) {
+                return;
+            }
+            std::fs::create_dir_all("../frames").ok();
+            let mut frame = Document::new().set("viewBox", (0, 0, grid.width, grid.height));
+            for tile in heatmap_tiles(nodes, expanded) {
+                frame = frame.add(tile);
+            }
+            svg::save(format!("../frames/frame-{:05}.svg", count), &frame).unwrap();
+        };
+    let result = grid.path(
+        MIN_STRAIGHT,
+        MAX_STRAIGHT,
+        false,
+        CONNECTIVITY,
+        Some(&mut frame_cb),
+    );
+    let path = result.path;
+
+    let mut document = Document::new().set("viewBox", (0, 0, maze.size.0, maze.size.1));
 
     let rect = element::Rectangle::new()
         .set("fill", "rgba(220, 220, 220, 1")
@@ -301,10 +662,29 @@ fn main() -> Result<(), MazeError> {
         document = document.add(rect);
     }
 
-    for (x, y) in path.iter() {
+    for tile in heatmap_tiles(&result.nodes, &result.expanded) {
+        document = document.add(tile);
+    }
+
+    // Step cost depends on whether a leg is orthogonal or diagonal, so the
+    // tint gradient must weigh each leg the same way `g_score` does.
+    let path_cost: i32 = path
+        .windows(2)
+        .map(|w| grid.cost_of(w[0]) * step_cost((w[0].0 - w[1].0, w[0].1 - w[1].1)))
+        .sum();
+
+    let mut acc = 0;
+    let mut prev = grid.start;
+    for (x, y) in path.iter().rev() {
+        let coord = (*x, *y);
+        if coord != grid.start {
+            acc += grid.cost_of(coord) * step_cost((coord.0 - prev.0, coord.1 - prev.1));
+        }
+        prev = coord;
+        let alpha = 0.3 + 0.5 * (acc as f64 / path_cost.max(1) as f64);
         let rect = element::Rectangle::new()
             .set("stroke", "rgba(255, 0, 0, 0.5")
-            .set("fill", "rgba(255, 0, 0, 0.5")
+            .set("fill", format!("rgba(255, 0, 0, {})", alpha))
             .set("stroke-width", "0.1")
             .set("x", *x)
             .set("y", *y)
@@ -346,3 +726,152 @@ fn main() -> Result<(), MazeError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_grid(start: (i32, i32), end: (i32, i32), width: i32, height: i32) -> Grid {
+        Grid {
+            start,
+            end,
+            width,
+            height,
+            walls: HashSet::new(),
+            costs: HashMap::new(),
+            keys: HashMap::new(),
+            doors: HashMap::new(),
+        }
+    }
+
+    /// Lengths of each consecutive same-direction run along `path`, which
+    /// `Grid::path` returns ordered from `end` to `start`.
+    fn run_lengths(path: &[(i32, i32)]) -> Vec<i32> {
+        let ordered: Vec<_> = path.iter().rev().copied().collect();
+        let mut runs = Vec::new();
+        let mut dir = None;
+        let mut run = 0;
+        for w in ordered.windows(2) {
+            let delta = (w[1].0 - w[0].0, w[1].1 - w[0].1);
+            if Some(delta) == dir {
+                run += 1;
+            } else {
+                if dir.is_some() {
+                    runs.push(run);
+                }
+                dir = Some(delta);
+                run = 1;
+            }
+        }
+        if dir.is_some() {
+            runs.push(run);
+        }
+        runs
+    }
+
+    #[test]
+    fn crucible_constraint_respects_run_length_bounds() {
+        let grid = empty_grid((0, 1), (5, 1), 6, 3);
+        let result = grid.path(1, 2, false, Connectivity::Four, None);
+
+        assert!(!result.path.is_empty(), "expected a path under the constraint");
+        for run in run_lengths(&result.path) {
+            assert!((1..=2).contains(&run), "run length {} outside [1, 2]", run);
+        }
+    }
+
+    #[test]
+    fn weighted_terrain_picks_cheaper_detour_over_shorter_expensive_route() {
+        let mut grid = empty_grid((0, 0), (2, 0), 3, 3);
+        grid.costs.insert((1, 0), 100);
+
+        let result = grid.path(0, i32::MAX, false, Connectivity::Four, None);
+
+        let end_cost = result
+            .nodes
+            .values()
+            .filter(|n| n.state.0 == grid.end)
+            .map(|n| n.g_score)
+            .min()
+            .expect("goal should be reachable");
+
+        assert_eq!(end_cost, 4 * ORTHOGONAL_COST);
+        assert!(!result.path.contains(&(1, 0)), "should detour around the expensive cell");
+    }
+
+    #[test]
+    fn locked_door_blocks_path_without_the_matching_key() {
+        let mut grid = empty_grid((0, 0), (2, 0), 3, 1);
+        grid.doors.insert((1, 0), 1);
+
+        let result = grid.path(0, i32::MAX, true, Connectivity::Four, None);
+
+        assert!(result.path.is_empty(), "door should block every route to the goal");
+    }
+
+    #[test]
+    fn locked_door_is_passable_once_the_matching_key_is_collected() {
+        let mut grid = empty_grid((0, 0), (2, 0), 3, 1);
+        grid.doors.insert((1, 0), 1);
+        grid.keys.insert((0, 0), 1);
+
+        let result = grid.path(0, i32::MAX, true, Connectivity::Four, None);
+
+        assert!(!result.path.is_empty(), "the key at the start should unlock the door");
+        assert!(result.path.contains(&(1, 0)));
+    }
+
+    #[test]
+    fn eight_connectivity_takes_the_diagonal_shortcut() {
+        let grid = empty_grid((0, 0), (2, 2), 3, 3);
+
+        let result = grid.path(0, i32::MAX, false, Connectivity::Eight, None);
+
+        let end_cost = result
+            .nodes
+            .values()
+            .filter(|n| n.state.0 == grid.end)
+            .map(|n| n.g_score)
+            .min()
+            .expect("goal should be reachable");
+
+        assert_eq!(end_cost, 2 * DIAGONAL_COST);
+    }
+
+    #[test]
+    fn eight_connectivity_rejects_cutting_a_wall_corner() {
+        let mut grid = empty_grid((0, 0), (1, 1), 2, 2);
+        grid.walls.insert((1, 0));
+        grid.walls.insert((0, 1));
+
+        let result = grid.path(0, i32::MAX, false, Connectivity::Eight, None);
+
+        assert!(
+            result.path.is_empty(),
+            "diagonal step should be blocked when both flanking cells are walls"
+        );
+    }
+
+    #[test]
+    fn search_result_reports_expanded_and_node_costs() {
+        let grid = empty_grid((0, 0), (3, 0), 4, 1);
+
+        let result = grid.path(0, i32::MAX, false, Connectivity::Four, None);
+
+        assert!(
+            result.expanded.contains(&grid.start),
+            "the start cell must be expanded before the goal can be found"
+        );
+        let node_coords = result.nodes.keys().map(|state| state.0).collect();
+        assert!(result.expanded.is_subset(&node_coords));
+
+        let end_cost = result
+            .nodes
+            .values()
+            .filter(|n| n.state.0 == grid.end)
+            .map(|n| n.g_score)
+            .min()
+            .expect("goal should be reachable");
+        assert_eq!(end_cost, 3 * ORTHOGONAL_COST);
+    }
+}